@@ -0,0 +1,46 @@
+// Hand-rolled argument parsing: the binary's surface is small enough that a CLI
+// crate would be more ceremony than the three subcommands it dispatches to.
+
+pub enum Command {
+    Run { dry_run: bool },
+    AddWebhook { source: String, url: String },
+    Test { source: String },
+}
+
+pub struct Args {
+    pub command: Command,
+    pub config_path: String,
+}
+
+const DEFAULT_CONFIG_PATH: &'static str = "comic_cron.json";
+
+pub fn parse() -> Result<Args, String> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut config_path = DEFAULT_CONFIG_PATH.to_string();
+    if let Some(pos) = args.iter().position(|a| a == "--config") {
+        let path = args.get(pos + 1).ok_or("--config requires a path".to_string())?.to_string();
+        args.remove(pos + 1);
+        args.remove(pos);
+        config_path = path;
+    }
+
+    let command = match args.get(0).map(String::as_str) {
+        None | Some("run") => {
+            let dry_run = args.iter().any(|a| a == "--dry-run");
+            Command::Run { dry_run }
+        }
+        Some("add-webhook") => {
+            let source = args.get(1).ok_or("usage: add-webhook <source> <url>".to_string())?.to_string();
+            let url = args.get(2).ok_or("usage: add-webhook <source> <url>".to_string())?.to_string();
+            Command::AddWebhook { source, url }
+        }
+        Some("test") => {
+            let source = args.get(1).ok_or("usage: test <source>".to_string())?.to_string();
+            Command::Test { source }
+        }
+        Some(other) => return Err(format!("unknown command: {}", other)),
+    };
+
+    Ok(Args { command, config_path })
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// One posted comic, shaped to double as a JSON Feed 1.1 item: the same struct is
+/// stored in `ComicCronState` (bounded history) and serialized straight into `feed.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub image: String,
+    pub content_html: String,
+    pub summary: String,
+    pub date_published: String,
+}
+
+#[derive(Serialize, Debug)]
+struct Feed<'a> {
+    version: &'static str,
+    title: &'static str,
+    home_page_url: &'static str,
+    items: &'a [FeedItem],
+}
+
+const FEED_VERSION: &'static str = "https://jsonfeed.org/version/1.1";
+const FEED_HOME_PAGE_URL: &'static str = "https://github.com/jearmstrong21/comic_cron";
+
+pub fn write(path: &str, items: &[FeedItem]) -> Result<(), String> {
+    let feed = Feed {
+        version: FEED_VERSION,
+        title: "ComicCron",
+        home_page_url: FEED_HOME_PAGE_URL,
+        items,
+    };
+    let text = serde_json::to_string_pretty(&feed).map_err(|_| "rust -> text".to_string())?;
+    std::fs::write(path, text).map_err(|_| "text -> filesystem".to_string())
+}
@@ -1,5 +1,9 @@
+mod cli;
+mod feed;
+
 use std::fmt::Display;
 use std::str::FromStr;
+use std::time::Duration;
 
 use chrono::TimeZone;
 use macky_xml::{Node, QuerySupport};
@@ -7,11 +11,13 @@ use reqwest::Client;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde::de::Error;
 
+use feed::FeedItem;
+
 fn from_str<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> where T::Err: Display {
     T::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Field {
     name: String,
     value: String,
@@ -53,7 +59,7 @@ struct Webhook {
     embeds: Vec<Embed>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Xkcd {
     #[serde(deserialize_with = "from_str")]
     month: u32,
@@ -82,15 +88,7 @@ struct RssItem {
 }
 
 impl RssItem {
-    fn qc_webhook(&self, potential_skip: bool) -> Option<Webhook> {
-        self.webhook(potential_skip, "QC", "https://www.questionablecontent.net/favicon/favicon-16x16.png")
-    }
-
-    fn smbc_webhook(&self, potential_skip: bool) -> Option<Webhook> {
-        self.webhook(potential_skip, "SMBC", "https://www.smbc-comics.com/favicon.ico")
-    }
-
-    fn webhook(&self, potential_skip: bool, embed_title: &'static str, footer: &'static str) -> Option<Webhook> {
+    fn webhook(&self, potential_skip: bool, embed_title: &str, footer_icon_url: &str) -> Option<Webhook> {
         Some(Webhook {
             content: if potential_skip { "Some items may have been skipped" } else { "" }.to_string(),
             username: format!("ComicCron {}", embed_title),
@@ -102,53 +100,205 @@ impl RssItem {
                 url: Some(self.link.to_owned()),
                 timestamp: chrono::DateTime::parse_from_rfc2822(&self.pub_date).ok()?.format("%+").to_string(),
                 fields: vec![],
-                footer: Some(Footer { text: self.alt_text.to_owned(), icon_url: Some(footer.to_string()) }),
+                footer: Some(Footer { text: self.alt_text.to_owned(), icon_url: Some(footer_icon_url.to_string()) }),
                 image: Some(Image { url: self.img_url.to_owned() }),
             }],
         })
     }
 
-    fn parse_qc_desc(data: Vec<&Node>) -> Option<(&str, &str)> {
-        let img_url = data.elem_name("img").first()?.attributes.get("src")?;
-        Some((img_url, ""))
-    }
-
-    fn parse_smbc_desc(data: Vec<&Node>) -> Option<(&str, &str)> {
-        let img_url = data.elem_name("img").first()?.attributes.get("src")?;
-        Some((img_url, ""))
+    fn feed_item(&self) -> Option<FeedItem> {
+        Some(FeedItem {
+            id: self.guid.to_string(),
+            url: self.link.to_string(),
+            title: self.title.to_string(),
+            image: self.img_url.to_string(),
+            content_html: format!("<img src=\"{}\">", self.img_url),
+            summary: self.alt_text.to_string(),
+            date_published: chrono::DateTime::parse_from_rfc2822(&self.pub_date).ok()?.format("%+").to_string(),
+        })
     }
 
     fn from_rss(item: &macky_xml::Element, description: impl Fn(Vec<&Node>) -> Option<(&str, &str)>) -> Option<RssItem> {
         let title = item.children().elem_name("title").only()?.children().only()?.as_cdata()?;
         let link = item.children().elem_name("link").only()?.children().only()?.as_cdata()?;
         let description_text = item.children().elem_name("description").only()?.children().only()?.as_cdata()?;
-        let description_parser = macky_xml::Parser {
-            allow_no_close: vec!["img".to_string(), "!doctype".to_string()]
-        };
-        let description_text = format!("<root>{}</root>", description_text);
-        let description_doc = description_parser.complete_element(&description_text)?;
-        let (img_url, alt_text) = description(description_doc.children())?;
+        let (img_url, alt_text) = parse_html_img(description_text, &description)?;
         let pub_date = item.children().elem_name("pubDate").only()?.children().only()?.as_cdata()?.to_owned();
         let guid = item.children().elem_name("guid").only()?.children().only()?.as_cdata()?.to_owned();
 
         Some(RssItem {
             title: title.to_string(),
             link: link.to_string(),
-            img_url: img_url.to_string(),
-            alt_text: alt_text.to_string(),
+            img_url,
+            alt_text,
+            pub_date,
+            guid,
+        })
+    }
+
+    fn from_atom(entry: &macky_xml::Element, description: impl Fn(Vec<&Node>) -> Option<(&str, &str)>) -> Option<RssItem> {
+        let title = entry.children().elem_name("title").only()?.children().only()?.as_cdata()?;
+        // Atom entries commonly carry several `<link>` elements (alternate, self,
+        // replies, enclosures); `rel="alternate"`, or no `rel` at all (the spec
+        // default), is the one that points at the human-readable strip.
+        let link = entry.children().elem_name("link").into_iter()
+            .find(|node| node.attributes.get("rel").map(|rel| rel.as_str()).unwrap_or("alternate") == "alternate")
+            .and_then(|node| node.attributes.get("href"))?;
+        let content_elem = entry.children().elem_name("content").only()
+            .or_else(|| entry.children().elem_name("summary").only())?;
+        let content_text = content_elem.children().only()?.as_cdata()?;
+        let (img_url, alt_text) = parse_html_img(content_text, &description)?;
+        let updated = entry.children().elem_name("updated").only()?.children().only()?.as_cdata()?;
+        let pub_date = chrono::DateTime::parse_from_rfc3339(updated).ok()?.to_rfc2822();
+        let guid = entry.children().elem_name("id").only()?.children().only()?.as_cdata()?.to_owned();
+
+        Some(RssItem {
+            title: title.to_string(),
+            link: link.to_string(),
+            img_url,
+            alt_text,
             pub_date,
             guid,
         })
     }
+
+    fn from_jsonfeed(text: &str, description: impl Fn(Vec<&Node>) -> Option<(&str, &str)>) -> Option<Vec<RssItem>> {
+        #[derive(Deserialize)]
+        struct JsonFeedDoc {
+            items: Vec<JsonFeedItem>,
+        }
+        #[derive(Deserialize)]
+        struct JsonFeedItem {
+            id: String,
+            url: String,
+            title: String,
+            #[serde(default)]
+            image: Option<String>,
+            date_published: String,
+            #[serde(default)]
+            content_html: String,
+        }
+
+        let doc: JsonFeedDoc = serde_json::from_str(text).ok()?;
+        let mut rss_items = vec![];
+        for item in &doc.items {
+            let (img_url, alt_text) = match &item.image {
+                Some(image) => (image.to_string(), String::new()),
+                None => parse_html_img(&item.content_html, &description)?,
+            };
+            let pub_date = chrono::DateTime::parse_from_rfc3339(&item.date_published).ok()?.to_rfc2822();
+            rss_items.push(RssItem {
+                title: item.title.to_string(),
+                link: item.url.to_string(),
+                img_url,
+                alt_text,
+                pub_date,
+                guid: item.id.to_string(),
+            });
+        }
+        Some(rss_items)
+    }
+}
+
+// Shared by `from_rss`/`from_atom`/`from_jsonfeed`: wraps an embedded HTML fragment
+// (an RSS `<description>`, an Atom `<content>`/`<summary>`, or a JSON Feed
+// `content_html`) in a throwaway root element so the same img-extraction closures
+// used for RSS can scan it.
+fn parse_html_img(html: &str, description: &impl Fn(Vec<&Node>) -> Option<(&str, &str)>) -> Option<(String, String)> {
+    let parser = macky_xml::Parser {
+        allow_no_close: vec!["img".to_string(), "!doctype".to_string()]
+    };
+    let wrapped = format!("<root>{}</root>", html);
+    let doc = parser.complete_element(&wrapped)?;
+    let (img_url, alt_text) = description(doc.children())?;
+    Some((img_url.to_string(), alt_text.to_string()))
 }
 
+const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+
 impl Webhook {
     async fn send(self, client: &reqwest::Client, webhooks: &Vec<String>) -> Result<(), String> {
         for url in webhooks {
-            client.post(url).json(&self).send().await.map_err(|_| "error sending webhook".to_string())?;
+            self.send_one(client, url).await?;
         }
         Ok(())
     }
+
+    // Retries on rate limiting and transient server/transport errors so a flaky
+    // Discord response doesn't silently drop a strip. Only a 2xx commits the send;
+    // everything else bubbles up so the caller doesn't advance its stored guid/num.
+    async fn send_one(&self, client: &reqwest::Client, url: &str) -> Result<(), String> {
+        let mut backoff = Duration::from_secs(1);
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let response = match client.post(url).json(self).send().await {
+                Ok(response) => response,
+                Err(_) if attempt < WEBHOOK_MAX_ATTEMPTS => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    continue;
+                }
+                Err(err) => return Err(format!("error sending webhook: {}", err)),
+            };
+
+            if response.status().is_success() {
+                return Ok(());
+            }
+
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                tokio::time::sleep(Webhook::retry_after(response, backoff).await).await;
+                continue;
+            }
+
+            if response.status().is_server_error() && attempt < WEBHOOK_MAX_ATTEMPTS {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                continue;
+            }
+
+            return Err(format!("webhook responded {}", response.status()));
+        }
+        Err("webhook exhausted retries".to_string())
+    }
+
+    // Discord sends the wait either as a `Retry-After` header or a `retry_after`
+    // field in the JSON body; fall back to the current backoff if neither parses
+    // (or if the server hands back something nonsensical, since `secs` is untrusted
+    // input and `Duration::from_secs_f64` panics on negative/NaN/infinite/overflowing
+    // values — `try_from_secs_f64` turns all of those into a plain `Err` instead).
+    async fn retry_after(response: reqwest::Response, backoff: Duration) -> Duration {
+        const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+        fn to_duration(secs: f64) -> Option<Duration> {
+            Duration::try_from_secs_f64(secs).ok().map(|d| d.min(MAX_RETRY_AFTER))
+        }
+
+        let header_secs = response.headers().get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(to_duration);
+        match header_secs {
+            Some(duration) => duration,
+            None => response.json::<serde_json::Value>().await.ok()
+                .and_then(|body| body.get("retry_after").and_then(|v| v.as_f64()))
+                .and_then(to_duration)
+                .unwrap_or(backoff),
+        }
+    }
+
+    // Routes through `send` normally; under `--dry-run` prints the JSON that would
+    // have been POSTed instead, so `run --dry-run` touches neither Discord nor state.
+    async fn dispatch(self, client: &reqwest::Client, webhooks: &Vec<String>, dry_run: bool) -> Result<(), String> {
+        if dry_run {
+            let text = serde_json::to_string_pretty(&self).map_err(|_| "rust -> text".to_string())?;
+            for _ in webhooks {
+                println!("{}", text);
+            }
+            Ok(())
+        } else {
+            self.send(client, webhooks).await
+        }
+    }
+
     fn debug(fields: Vec<Field>) -> Webhook {
         Webhook {
             content: "".to_string(),
@@ -170,21 +320,52 @@ impl Webhook {
 
 impl Xkcd {
     async fn get(client: &reqwest::Client, index: Option<i32>) -> Result<Xkcd, String> {
+        let (xkcd, _, _) = Xkcd::get_conditional(client, index, None, None).await?;
+        xkcd.ok_or_else(|| format!("unexpected 304 for {:?}", index))
+    }
+
+    async fn get_conditional(client: &reqwest::Client, index: Option<i32>, etag: Option<&str>, last_modified: Option<&str>) -> Result<(Option<Xkcd>, Option<String>, Option<String>), String> {
         let url = match index {
             Some(index) => format!("https://xkcd.com/{}/info.0.json", index),
             None => "https://xkcd.com/info.0.json".to_string()
         };
-        let response = client.get(&url).send().await.map_err(|_| format!("url -> request {:?}", index))?;
+        let mut request = client.get(&url);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let response = request.send().await.map_err(|_| format!("url -> request {:?}", index))?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok((None, None, None));
+        }
+        let (new_etag, new_last_modified) = caching_headers(&response);
         let text = response.text().await.map_err(|_| format!("request -> text: #{:?}", index))?;
         let json = serde_json::Value::from_str(&text).map_err(|_| format!("text -> json {:?}", index))?;
-        serde_json::from_value(json).map_err(|_| format!("json -> rust {:?}", index))
+        let xkcd = serde_json::from_value(json).map_err(|_| format!("json -> rust {:?}", index))?;
+        Ok((Some(xkcd), new_etag, new_last_modified))
+    }
+
+    fn feed_item(&self) -> FeedItem {
+        FeedItem {
+            id: self.num.to_string(),
+            url: self.link.to_string(),
+            title: format!("#{}: {}", self.num, self.title),
+            image: self.img.to_string(),
+            content_html: format!("<img src=\"{}\">", self.img),
+            summary: self.alt.to_string(),
+            date_published: chrono::Utc.ymd(self.year, self.month, self.day).and_hms(0, 0, 0).format("%+").to_string(),
+        }
     }
 }
 
-impl Into<Webhook> for Xkcd {
-    fn into(self) -> Webhook {
+impl Xkcd {
+    // Mirrors `RssItem::webhook`'s `potential_skip` flag so a capped xkcd backfill
+    // tells subscribers the same way a capped RSS backfill does.
+    fn webhook(self, potential_skip: bool) -> Webhook {
         Webhook {
-            content: "".to_string(),
+            content: if potential_skip { "Some items may have been skipped" } else { "" }.to_string(),
             username: "ComicCron xkcd".to_string(),
             avatar_url: AVATAR_URL.to_string(),
             embeds: vec![Embed {
@@ -201,26 +382,113 @@ impl Into<Webhook> for Xkcd {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// Selects which closure `RssItem::from_rss` uses to pull the image/alt text out of a
+// source's `<description>` markup. Add a variant here (and a matching arm below) to
+// onboard a comic whose feed doesn't already fit the `img[src]` shape.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+enum DescriptionParser {
+    ImgSrc,
+}
+
+impl DescriptionParser {
+    fn parse<'a>(&self, data: Vec<&'a Node>) -> Option<(&'a str, &'a str)> {
+        match self {
+            DescriptionParser::ImgSrc => {
+                let img_url = data.elem_name("img").first()?.attributes.get("src")?;
+                Some((img_url, ""))
+            }
+        }
+    }
+}
+
+// Which shape `poll_rss` should parse `feed_url`'s response as.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+enum FeedFormat {
+    Rss2,
+    Atom,
+    JsonFeed,
+}
+
+fn default_feed_format() -> FeedFormat {
+    FeedFormat::Rss2
+}
+
+// One entry per comic. Onboarding a new comic is just appending one of these to
+// `ComicCronState::sources` in `comic_cron.json` rather than writing a new poller.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ComicSource {
+    name: String,
+    feed_url: String,
+    embed_title: String,
+    footer_icon_url: String,
+    #[serde(default = "default_feed_format")]
+    format: FeedFormat,
+    description_parser: DescriptionParser,
+    last_guid: String,
+    webhooks: Vec<String>,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+    // When true, a run that is several items behind posts the missed items in
+    // chronological order instead of just the one adjacent to `last_guid`. At most
+    // `max_backfill_per_run` of them are posted, the newest ones; older missed items
+    // are skipped and the first post says that items may have been skipped.
+    #[serde(default)]
+    backfill: bool,
+    #[serde(default = "default_max_backfill_per_run")]
+    max_backfill_per_run: usize,
+}
+
+fn default_max_backfill_per_run() -> usize {
+    5
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct ComicCronState {
     xkcd: i32,
-    qc: String,
-    smbc: String,
     xkcd_webhooks: Vec<String>,
-    qc_webhooks: Vec<String>,
-    smbc_webhooks: Vec<String>,
+    #[serde(default)]
+    xkcd_etag: Option<String>,
+    #[serde(default)]
+    xkcd_last_modified: Option<String>,
+    #[serde(default)]
+    xkcd_backfill: bool,
+    #[serde(default = "default_max_backfill_per_run")]
+    xkcd_max_backfill_per_run: usize,
+    sources: Vec<ComicSource>,
     debug_webhooks: Vec<String>,
+    // Bounded so `feed.json` stays a reasonable size across a long-running cron.
+    #[serde(default)]
+    feed_items: Vec<FeedItem>,
 }
 
+const FEED_HISTORY_LIMIT: usize = 50;
+
 impl ComicCronState {
-    fn get() -> Result<ComicCronState, String> {
-        let text = std::fs::read_to_string("comic_cron.json").map_err(|_| "filesystem -> text".to_string())?;
+    fn get(path: &str) -> Result<ComicCronState, String> {
+        let text = std::fs::read_to_string(path).map_err(|_| "filesystem -> text".to_string())?;
         let json = serde_json::Value::from_str(&text).map_err(|_| "text -> json".to_string())?;
         serde_json::from_value(json).map_err(|_| "json -> rust".to_string())
     }
-    fn set(&self) -> Result<(), String> {
+    fn set(&self, path: &str) -> Result<(), String> {
         let text = serde_json::to_string_pretty(&self).map_err(|_| "rust -> text".to_string())?;
-        std::fs::write("comic_cron.json", text).map_err(|_| "text -> filesystem".to_string())
+        std::fs::write(path, text).map_err(|_| "text -> filesystem".to_string())
+    }
+    fn push_feed_item(&mut self, item: FeedItem) {
+        self.feed_items.push(item);
+    }
+    // Re-sorts the whole history newest-first by actual instant (not string order,
+    // since `date_published` carries each source's own UTC offset) and re-applies
+    // `FEED_HISTORY_LIMIT`. Needed because a single run can post items from several
+    // sources, or several backfilled items from one source, in non-chronological
+    // iteration order.
+    fn sort_feed_items(&mut self) {
+        self.feed_items.sort_by_key(|item| std::cmp::Reverse(chrono::DateTime::parse_from_rfc3339(&item.date_published).ok()));
+        self.feed_items.truncate(FEED_HISTORY_LIMIT);
+    }
+    fn find_source_mut(&mut self, name: &str) -> Option<&mut ComicSource> {
+        self.sources.iter_mut().find(|source| source.name.eq_ignore_ascii_case(name))
     }
 }
 
@@ -228,129 +496,272 @@ const AVATAR_URL: &'static str = "https://cdn.discordapp.com/attachments/7519980
 
 type Success = Option<String>;
 
-async fn xkcd(client: &Client, state: &mut ComicCronState) -> Result<Success, String> {
-    let latest_xkcd = Xkcd::get(client, None).await?;
+fn caching_headers(response: &reqwest::Response) -> (Option<String>, Option<String>) {
+    let etag = response.headers().get(reqwest::header::ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let last_modified = response.headers().get(reqwest::header::LAST_MODIFIED).and_then(|v| v.to_str().ok()).map(str::to_string);
+    (etag, last_modified)
+}
+
+// Parses a fetched feed body according to `source.format` into the feed-agnostic
+// `RssItem` shape. Shared by `poll_rss` and the `test` subcommand, which both need
+// "the newest items" without caring how they were served.
+fn parse_feed_items(text: &str, source: &ComicSource) -> Result<Vec<RssItem>, String> {
+    let rss_items = match source.format {
+        FeedFormat::Rss2 => {
+            let document = macky_xml::Parser::default().complete_document(text).ok_or("text -> xml".to_string())?;
+            let xml_items = document.root.children().elem_name("item");
+            if xml_items.len() == 0 {
+                return Err("no rss items".to_string());
+            }
+            let mut items = vec![];
+            for xml in xml_items {
+                items.push(RssItem::from_rss(xml, |data| source.description_parser.parse(data)).ok_or("xml -> rust")?);
+            }
+            items
+        }
+        FeedFormat::Atom => {
+            let document = macky_xml::Parser::default().complete_document(text).ok_or("text -> xml".to_string())?;
+            let xml_entries = document.root.children().elem_name("entry");
+            if xml_entries.len() == 0 {
+                return Err("no atom entries".to_string());
+            }
+            let mut items = vec![];
+            for xml in xml_entries {
+                items.push(RssItem::from_atom(xml, |data| source.description_parser.parse(data)).ok_or("xml -> rust")?);
+            }
+            items
+        }
+        FeedFormat::JsonFeed => {
+            RssItem::from_jsonfeed(text, |data| source.description_parser.parse(data)).ok_or("json -> rust".to_string())?
+        }
+    };
+    if rss_items.is_empty() {
+        return Err("no feed items".to_string());
+    }
+    Ok(rss_items)
+}
+
+async fn xkcd(client: &Client, state: &mut ComicCronState, feed_items: &mut Vec<FeedItem>, dry_run: bool) -> Result<Success, String> {
+    let (latest, etag, last_modified) = Xkcd::get_conditional(client, None, state.xkcd_etag.as_deref(), state.xkcd_last_modified.as_deref()).await?;
+    if etag.is_some() || last_modified.is_some() {
+        state.xkcd_etag = etag;
+        state.xkcd_last_modified = last_modified;
+    }
+    let latest_xkcd = match latest {
+        Some(latest_xkcd) => latest_xkcd,
+        None => return Ok(None),
+    };
+    if state.xkcd + 1 > latest_xkcd.num {
+        return Ok(None);
+    }
+
+    if state.xkcd_backfill {
+        let total = (latest_xkcd.num - state.xkcd) as usize;
+        let capped = total > state.xkcd_max_backfill_per_run;
+        let start = if capped { latest_xkcd.num - state.xkcd_max_backfill_per_run as i32 + 1 } else { state.xkcd + 1 };
+        let mut last_num = None;
+        for (idx, n) in (start..=latest_xkcd.num).enumerate() {
+            let post = if n == latest_xkcd.num { latest_xkcd.clone() } else { Xkcd::get(client, Some(n)).await? };
+            let num = post.num.to_string();
+            feed_items.push(post.feed_item());
+            let potential_skip = capped && idx == 0;
+            let webhook = post.webhook(potential_skip);
+            webhook.dispatch(client, &state.xkcd_webhooks, dry_run).await?;
+            state.xkcd = n;
+            last_num = Some(num);
+        }
+        return Ok(last_num);
+    }
+
     let post = if state.xkcd + 1 == latest_xkcd.num {
         latest_xkcd
-    } else if state.xkcd + 1 < latest_xkcd.num {
-        Xkcd::get(client, Some(state.xkcd + 1)).await?
     } else {
-        return Ok(None);
+        Xkcd::get(client, Some(state.xkcd + 1)).await?
     };
     let num = post.num.to_string();
-    let webhook: Webhook = post.into();
-    webhook.send(client, &state.xkcd_webhooks).await?;
+    feed_items.push(post.feed_item());
+    let webhook = post.webhook(false);
+    webhook.dispatch(client, &state.xkcd_webhooks, dry_run).await?;
     state.xkcd += 1;
     Ok(Some(num))
 }
 
-async fn qc(client: &Client, state: &mut ComicCronState) -> Result<Success, String> {
-    let response = client.get("https://www.questionablecontent.net/QCRSS.xml").send().await.map_err(|_| "url -> request".to_string())?;
+async fn poll_rss(client: &Client, source: &mut ComicSource, feed_items: &mut Vec<FeedItem>, dry_run: bool) -> Result<Success, String> {
+    let mut request = client.get(&source.feed_url);
+    if let Some(etag) = &source.etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &source.last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send().await.map_err(|_| "url -> request".to_string())?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    let (etag, last_modified) = caching_headers(&response);
+    source.etag = etag;
+    source.last_modified = last_modified;
     let text = response.text().await.map_err(|_| "request -> text".to_string())?;
-    let document = macky_xml::Parser::default().complete_document(&text).ok_or("text -> xml".to_string())?;
-    let xml_items = document.root.children().elem_name("item");
-    if xml_items.len() == 0 {
-        Err("no rss items".to_string())
-    } else {
-        let mut rss_items = vec![];
-        for xml in xml_items {
-            rss_items.push(RssItem::from_rss(xml, RssItem::parse_qc_desc).ok_or("xml -> rust")?);
+    let rss_items = parse_feed_items(&text, source)?;
+    if rss_items[0].guid == source.last_guid {
+        return Ok(None);
+    }
+
+    // Items are newest-first; `found_at` is how far back the stored guid still appears.
+    let found_at = rss_items.iter().position(|item| item.guid == source.last_guid);
+
+    if source.backfill {
+        let mut unseen: Vec<&RssItem> = match found_at {
+            Some(pos) => rss_items[..pos].iter().collect(),
+            None => rss_items.iter().collect(),
+        };
+        unseen.reverse(); // oldest-unseen first
+        let capped = unseen.len() > source.max_backfill_per_run || found_at.is_none();
+        if unseen.len() > source.max_backfill_per_run {
+            unseen = unseen.split_off(unseen.len() - source.max_backfill_per_run);
         }
-        if rss_items[0].guid == state.qc {
-            Ok(None)
-        } else {
-            for i in 1..rss_items.len() {
-                if rss_items[i].guid == state.qc {
-                    let webhook = rss_items[i - 1].qc_webhook(false).ok_or("rust -> webhook".to_string())?;
-                    webhook.send(client, &state.qc_webhooks).await?;
-                    state.qc = rss_items[i - 1].guid.to_string();
-                    return Ok(Some(rss_items[i - 1].title.to_string()));
-                }
+        let mut last_title = None;
+        for (idx, item) in unseen.into_iter().enumerate() {
+            let potential_skip = capped && idx == 0;
+            let webhook = item.webhook(potential_skip, &source.embed_title, &source.footer_icon_url).ok_or("rust -> webhook".to_string())?;
+            webhook.dispatch(client, &source.webhooks, dry_run).await?;
+            if let Some(feed_item) = item.feed_item() {
+                feed_items.push(feed_item);
             }
-            let webhook = rss_items[rss_items.len() - 1].qc_webhook(true).ok_or("rust -> webhook".to_string())?;
-            webhook.send(client, &state.qc_webhooks).await?;
-            state.qc = rss_items[rss_items.len() - 1].guid.to_string();
-            Ok(Some(rss_items[rss_items.len() - 1].title.to_string()))
+            source.last_guid = item.guid.to_string();
+            last_title = Some(item.title.to_string());
         }
+        return Ok(last_title);
     }
-}
 
-async fn smbc(client: &Client, state: &mut ComicCronState) -> Result<Success, String> {
-    let response = client.get("https://www.smbc-comics.com/comic/rss").send().await.map_err(|_| "url -> request".to_string())?;
-    let text = response.text().await.map_err(|_| "request -> text".to_string())?;
-    let document = macky_xml::Parser::default().complete_document(&text).ok_or("text -> xml".to_string())?;
-    let xml_items = document.root.children().elem_name("item");
-    if xml_items.len() == 0 {
-        Err("no rss items".to_string())
-    } else {
-        let mut rss_items = vec![];
-        for xml in xml_items {
-            rss_items.push(RssItem::from_rss(xml, RssItem::parse_smbc_desc).ok_or("xml -> rust")?);
+    match found_at {
+        Some(pos) => {
+            let webhook = rss_items[pos - 1].webhook(false, &source.embed_title, &source.footer_icon_url).ok_or("rust -> webhook".to_string())?;
+            webhook.dispatch(client, &source.webhooks, dry_run).await?;
+            if let Some(feed_item) = rss_items[pos - 1].feed_item() {
+                feed_items.push(feed_item);
+            }
+            source.last_guid = rss_items[pos - 1].guid.to_string();
+            Ok(Some(rss_items[pos - 1].title.to_string()))
         }
-        if rss_items[0].guid == state.smbc {
-            Ok(None)
-        } else {
-            for i in 1..rss_items.len() {
-                if rss_items[i].guid == state.smbc {
-                    let webhook = rss_items[i - 1].smbc_webhook(false).ok_or("rust -> webhook".to_string())?;
-                    webhook.send(client, &state.smbc_webhooks).await?;
-                    state.smbc = rss_items[i - 1].guid.to_string();
-                    return Ok(Some(rss_items[i - 1].title.to_string()));
+        None => {
+            let last = rss_items.len() - 1;
+            let webhook = rss_items[last].webhook(true, &source.embed_title, &source.footer_icon_url).ok_or("rust -> webhook".to_string())?;
+            webhook.dispatch(client, &source.webhooks, dry_run).await?;
+            if let Some(feed_item) = rss_items[last].feed_item() {
+                feed_items.push(feed_item);
+            }
+            source.last_guid = rss_items[last].guid.to_string();
+            Ok(Some(rss_items[last].title.to_string()))
+        }
+    }
+}
+
+// `feed.json` lives next to the config file rather than the process's CWD, so
+// `--config /other/dir/comic_cron.json` doesn't scatter state across directories.
+fn feed_path(config_path: &str) -> std::path::PathBuf {
+    std::path::Path::new(config_path).with_file_name("feed.json")
+}
+
+async fn run(config_path: &str, dry_run: bool) {
+    match ComicCronState::get(config_path) {
+        Ok(mut state) => {
+            let client = reqwest::Client::new();
+            let mut fields = vec![];
+            let mut new_feed_items = vec![];
+
+            let xkcd_result = format!("{:?}", xkcd(&client, &mut state, &mut new_feed_items, dry_run).await);
+            fields.push(Field { name: "xkcd".to_string(), value: format!("`{}`", xkcd_result), inline: false });
+
+            for source in &mut state.sources {
+                let result = format!("{:?}", poll_rss(&client, source, &mut new_feed_items, dry_run).await);
+                fields.push(Field { name: source.name.to_string(), value: format!("`{}`", result), inline: false });
+            }
+
+            for item in new_feed_items {
+                state.push_feed_item(item);
+            }
+            state.sort_feed_items();
+
+            if dry_run {
+                fields.push(Field { name: "Save".to_string(), value: "`skipped (--dry-run)`".to_string(), inline: false });
+            } else {
+                let save = format!("{:?}", state.set(config_path));
+                fields.push(Field { name: "Save".to_string(), value: format!("`{}`", save), inline: false });
+
+                let feed_save = format!("{:?}", feed::write(&feed_path(config_path).to_string_lossy(), &state.feed_items));
+                fields.push(Field { name: "Feed".to_string(), value: format!("`{}`", feed_save), inline: false });
+            }
+
+            if let Err(err) = Webhook::debug(fields.clone()).dispatch(&client, &state.debug_webhooks, dry_run).await {
+                for field in &fields {
+                    println!("{}: {}", field.name, field.value);
                 }
+                println!("Error sending debug webhook:\n{}", err);
             }
-            let webhook = rss_items[rss_items.len() - 1].smbc_webhook(true).ok_or("rust -> webhook".to_string())?;
-            webhook.send(client, &state.smbc_webhooks).await?;
-            state.smbc = rss_items[rss_items.len() - 1].guid.to_string();
-            Ok(Some(rss_items[rss_items.len() - 1].title.to_string()))
+        }
+        Err(err) => {
+            println!("Error loading state:\n{}", err);
         }
     }
 }
 
+fn add_webhook(config_path: &str, source_name: &str, url: &str) -> Result<(), String> {
+    let mut state = ComicCronState::get(config_path)?;
+    if source_name.eq_ignore_ascii_case("xkcd") {
+        state.xkcd_webhooks.push(url.to_string());
+    } else {
+        let source = state.find_source_mut(source_name).ok_or(format!("no such source: {}", source_name))?;
+        source.webhooks.push(url.to_string());
+    }
+    state.set(config_path)
+}
+
+// Forces a post of the latest strip to `debug_webhooks` regardless of `last_guid`/`xkcd`,
+// without touching the stored state, so operators can sanity-check a webhook URL.
+async fn test_source(config_path: &str, source_name: &str) -> Result<(), String> {
+    let state = ComicCronState::get(config_path)?;
+    let client = reqwest::Client::new();
+
+    let webhook = if source_name.eq_ignore_ascii_case("xkcd") {
+        let latest = Xkcd::get(&client, None).await?;
+        latest.webhook(false)
+    } else {
+        let source = state.sources.iter().find(|source| source.name.eq_ignore_ascii_case(source_name)).ok_or(format!("no such source: {}", source_name))?;
+        let response = client.get(&source.feed_url).send().await.map_err(|_| "url -> request".to_string())?;
+        let text = response.text().await.map_err(|_| "request -> text".to_string())?;
+        let rss_items = parse_feed_items(&text, source)?;
+        rss_items[0].webhook(false, &source.embed_title, &source.footer_icon_url).ok_or("rust -> webhook".to_string())?
+    };
+
+    webhook.send(&client, &state.debug_webhooks).await
+}
+
 fn main() {
     tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
         .unwrap()
         .block_on(async {
-            match ComicCronState::get() {
-                Ok(mut state) => {
-                    let client = reqwest::Client::new();
-                    let xkcd = format!("{:?}", xkcd(&client, &mut state).await);
-                    let qc = format!("{:?}", qc(&client, &mut state).await);
-                    let smbc = format!("{:?}", smbc(&client, &mut state).await);
-                    let save = format!("{:?}", state.set());
-
-                   if let Err(err) = Webhook::debug(vec![
-                        Field {
-                            name: "xkcd".to_string(),
-                            value: format!("`{}`", xkcd),
-                            inline: false,
-                        },
-                        Field {
-                            name: "QC".to_string(),
-                            value: format!("`{}`", qc),
-                            inline: false,
-                        },
-                        Field {
-                            name: "SMBC".to_string(),
-                            value: format!("`{}`", smbc),
-                            inline: false,
-                        },
-                        Field {
-                            name: "Save".to_string(),
-                            value: format!("`{}`", save),
-                            inline: false,
-                        }
-                    ]).send(&client, &state.debug_webhooks).await {
-                        println!("xkcd: {:?}", xkcd);
-                        println!("qc  : {:?}", qc);
-                        println!("smbc: {:?}", smbc);
-                        println!("save: {:?}", save);
-                        println!("Error sending debug webhook:\n{}", err);
+            let args = match cli::parse() {
+                Ok(args) => args,
+                Err(err) => {
+                    println!("{}", err);
+                    return;
+                }
+            };
+
+            match args.command {
+                cli::Command::Run { dry_run } => run(&args.config_path, dry_run).await,
+                cli::Command::AddWebhook { source, url } => {
+                    if let Err(err) = add_webhook(&args.config_path, &source, &url) {
+                        println!("Error adding webhook:\n{}", err);
                     }
                 }
-                Err(err) => {
-                    println!("Error loading state:\n{}", err);
+                cli::Command::Test { source } => {
+                    if let Err(err) = test_source(&args.config_path, &source).await {
+                        println!("Error testing source:\n{}", err);
+                    }
                 }
             }
         });